@@ -2,8 +2,7 @@ use std::{path::Path, rc::Rc, sync::atomic::AtomicU32};
 
 use itertools::Itertools;
 use wgpu::{
-    util::DeviceExt, BindGroup, BindGroupLayout, Buffer, PipelineLayout, RenderPass,
-    RenderPipeline, Sampler, ShaderModule,
+    util::DeviceExt, BindGroup, BindGroupLayout, Buffer, RenderPass, RenderPipeline, Sampler,
 };
 
 use crate::{
@@ -17,11 +16,16 @@ pub struct Sprite {
 }
 
 impl Sprite {
-    pub fn load_data<S>(canvas: &mut Canvas, rgba: &[u8], dimensions: S) -> Self
+    pub fn load_data<S>(
+        canvas: &mut Canvas,
+        rgba: &[u8],
+        dimensions: S,
+        options: SamplerOptions,
+    ) -> Self
     where
         S: Into<Size> + Copy,
     {
-        let texture = Rc::new(Texture::new(canvas, rgba, dimensions));
+        let texture = Rc::new(Texture::new(canvas, rgba, dimensions, options));
         let tex_coord = Rect {
             left: 0.0,
             top: 0.0,
@@ -36,7 +40,11 @@ impl Sprite {
         }
     }
 
-    pub fn load_image<P: AsRef<Path>>(canvas: &mut Canvas, path: P) -> Option<Self> {
+    pub fn load_image<P: AsRef<Path>>(
+        canvas: &mut Canvas,
+        path: P,
+        options: SamplerOptions,
+    ) -> Option<Self> {
         let image = image::open(path).ok()?;
 
         let rgba = image.as_rgba8()?;
@@ -44,7 +52,7 @@ impl Sprite {
         use image::GenericImageView;
         let dimensions = image.dimensions();
 
-        Some(Sprite::load_data(canvas, rgba, dimensions))
+        Some(Sprite::load_data(canvas, rgba, dimensions, options))
     }
 }
 
@@ -60,12 +68,13 @@ impl TileSet {
         rgba: &[u8],
         dimensions: S,
         tile_dimensions: TS,
+        options: SamplerOptions,
     ) -> Self
     where
         S: Into<Size> + Copy,
         TS: Into<Size> + Copy,
     {
-        let texture = Rc::new(Texture::new(canvas, rgba, dimensions));
+        let texture = Rc::new(Texture::new(canvas, rgba, dimensions, options));
 
         TileSet {
             dimensions: dimensions.into(),
@@ -74,7 +83,12 @@ impl TileSet {
         }
     }
 
-    pub fn load_image<P, S>(canvas: &mut Canvas, path: P, tile_dimensions: S) -> Option<Self>
+    pub fn load_image<P, S>(
+        canvas: &mut Canvas,
+        path: P,
+        tile_dimensions: S,
+        options: SamplerOptions,
+    ) -> Option<Self>
     where
         P: AsRef<Path>,
         S: Into<Size> + Copy,
@@ -91,6 +105,7 @@ impl TileSet {
             rgba,
             dimensions.into(),
             tile_dimensions,
+            options,
         ))
     }
 
@@ -117,35 +132,102 @@ impl TileSet {
     }
 }
 
+/// How a texture is filtered and addressed once it is loaded.
+///
+/// Use [`SamplerOptions::pixelated`] for crisp pixel art (nearest filtering,
+/// a single mip level) or [`SamplerOptions::smooth`] for sprites that are
+/// scaled down (linear filtering backed by a generated mipmap chain). The
+/// address mode can be switched to repeating with [`SamplerOptions::repeated`].
+#[derive(Clone, Copy, Debug)]
+pub struct SamplerOptions {
+    filter: wgpu::FilterMode,
+    mipmaps: bool,
+    address_mode: wgpu::AddressMode,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        SamplerOptions::pixelated()
+    }
+}
+
+impl SamplerOptions {
+    pub fn pixelated() -> Self {
+        SamplerOptions {
+            filter: wgpu::FilterMode::Nearest,
+            mipmaps: false,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+
+    pub fn smooth() -> Self {
+        SamplerOptions {
+            filter: wgpu::FilterMode::Linear,
+            mipmaps: true,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+
+    pub fn repeated(mut self) -> Self {
+        self.address_mode = wgpu::AddressMode::Repeat;
+        self
+    }
+
+    fn mip_level_count(&self, dimensions: Size) -> u32 {
+        if self.mipmaps {
+            let max = dimensions.width.max(dimensions.height).max(1);
+            // floor(log2(max)) + 1
+            32 - max.leading_zeros()
+        } else {
+            1
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub(crate) struct TextureId(u32);
 
+fn next_texture_id() -> TextureId {
+    static INDEX: AtomicU32 = AtomicU32::new(0);
+    TextureId(INDEX.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+}
+
 pub(crate) struct Texture {
     pub id: TextureId,
     pub texture_bind_group: BindGroup,
-    pub render_pipeline: RenderPipeline,
 }
 
 impl Texture {
-    fn new<S: Into<Size>>(canvas: &Canvas, rgba: &[u8], dimensions: S) -> Self {
-        static INDEX: AtomicU32 = AtomicU32::new(0);
-        let id = INDEX.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    fn new<S: Into<Size>>(
+        canvas: &Canvas,
+        rgba: &[u8],
+        dimensions: S,
+        options: SamplerOptions,
+    ) -> Self {
+        let id = next_texture_id();
         let dimensions: Size = dimensions.into();
         let texture_size = wgpu::Extent3d {
             width: dimensions.width,
             height: dimensions.height,
             depth_or_array_layers: 1,
         };
+        let mip_level_count = options.mip_level_count(dimensions);
+        // Generating the mipmap chain renders into the smaller levels, so the
+        // texture has to be usable as a render attachment when mipmaps are on.
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
         let wgpu_texture = canvas
             .wgpu_context
             .device
             .create_texture(&wgpu::TextureDescriptor {
                 size: texture_size,
-                mip_level_count: 1,
+                mip_level_count,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                usage,
                 label: Some("texture"),
             });
 
@@ -168,8 +250,29 @@ impl Texture {
             texture_size,
         );
 
+        if mip_level_count > 1 {
+            canvas.texture_renderer.generate_mipmaps(
+                &canvas.wgpu_context,
+                &wgpu_texture,
+                mip_level_count,
+            );
+        }
+
         let texture_view = wgpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
+        let sampler = canvas
+            .wgpu_context
+            .device
+            .create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: options.address_mode,
+                address_mode_v: options.address_mode,
+                address_mode_w: options.address_mode,
+                mag_filter: options.filter,
+                min_filter: options.filter,
+                mipmap_filter: options.filter,
+                ..Default::default()
+            });
+
         let texture_bind_group: BindGroup =
             canvas
                 .wgpu_context
@@ -183,77 +286,158 @@ impl Texture {
                         },
                         wgpu::BindGroupEntry {
                             binding: 1,
-                            resource: wgpu::BindingResource::Sampler(
-                                &canvas.texture_renderer.sampler,
-                            ),
+                            resource: wgpu::BindingResource::Sampler(&sampler),
                         },
                     ],
                     label: Some("diffuse_bind_group"),
                 });
 
-        let render_pipeline =
+        Texture {
+            id,
+            texture_bind_group,
+        }
+    }
+
+    /// Allocates an empty color texture usable both as a render attachment and
+    /// as a sampled texture, returning the [`Texture`] alongside the view that
+    /// a render pass draws into.
+    fn render_target(
+        canvas: &Canvas,
+        dimensions: Size,
+        options: SamplerOptions,
+    ) -> (Self, wgpu::TextureView) {
+        let id = next_texture_id();
+        let texture_size = wgpu::Extent3d {
+            width: dimensions.width,
+            height: dimensions.height,
+            depth_or_array_layers: 1,
+        };
+        let wgpu_texture = canvas
+            .wgpu_context
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                size: texture_size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                // Match the main pass format so the shared pipeline can target it.
+                format: canvas.wgpu_context.config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                label: Some("render_target"),
+            });
+
+        let texture_view = wgpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = canvas
+            .wgpu_context
+            .device
+            .create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: options.address_mode,
+                address_mode_v: options.address_mode,
+                address_mode_w: options.address_mode,
+                mag_filter: options.filter,
+                min_filter: options.filter,
+                mipmap_filter: options.filter,
+                ..Default::default()
+            });
+
+        let texture_bind_group =
             canvas
                 .wgpu_context
                 .device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Texture Render Pipeline"),
-                    layout: Some(&canvas.texture_renderer.render_pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &canvas.texture_renderer.shader,
-                        entry_point: "vs_main",                   // 1.
-                        buffers: &[TextureVertex::description()], // 2.
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        // 3.
-                        module: &canvas.texture_renderer.shader,
-                        entry_point: "fs_main",
-                        targets: &[wgpu::ColorTargetState {
-                            // 4.
-                            format: canvas.wgpu_context.config.format,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        }],
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList, // 1.
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw, // 2.
-                        cull_mode: Some(wgpu::Face::Back),
-                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        // Requires Features::DEPTH_CLAMPING
-                        clamp_depth: false,
-                        // Requires Features::CONSERVATIVE_RASTERIZATION
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: DepthTexture::DEPTH_FORMAT,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::GreaterEqual, // 1.
-                        stencil: wgpu::StencilState::default(),             // 2.
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: 1,                         // 2.
-                        mask: !0,                         // 3.
-                        alpha_to_coverage_enabled: false, // 4.
-                    },
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &canvas.texture_renderer.texture_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&texture_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                    label: Some("render_target_bind_group"),
                 });
 
-        let texture = Texture {
-            id: TextureId(id),
-            texture_bind_group,
-            render_pipeline,
-        };
-        texture
+        (
+            Texture {
+                id,
+                texture_bind_group,
+            },
+            texture_view,
+        )
+    }
+}
+
+/// An offscreen color target that a [`Canvas`] can render into, then expose as
+/// a [`Sprite`] for compositing the result back into another pass.
+pub struct RenderTarget {
+    pub(crate) dimensions: Size,
+    pub(crate) view: wgpu::TextureView,
+    pub(crate) depth_view: wgpu::TextureView,
+    texture: Rc<Texture>,
+    tex_coords: Rect,
+}
+
+impl RenderTarget {
+    pub fn new<S: Into<Size> + Copy>(
+        canvas: &Canvas,
+        dimensions: S,
+        options: SamplerOptions,
+    ) -> Self {
+        let dimensions: Size = dimensions.into();
+        let (texture, view) = Texture::render_target(canvas, dimensions, options);
+
+        // The shared sprite pipeline mandates a depth attachment sized to the
+        // color target, so each target carries its own matching depth buffer.
+        let depth_texture = canvas
+            .wgpu_context
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: dimensions.width,
+                    height: dimensions.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: DepthTexture::DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                label: Some("render_target_depth"),
+            });
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        RenderTarget {
+            dimensions,
+            view,
+            depth_view,
+            texture: Rc::new(texture),
+            tex_coords: Rect {
+                left: 0.0,
+                top: 0.0,
+                right: 1.0,
+                bottom: 1.0,
+            },
+        }
+    }
+
+    /// A sprite that samples the rendered result, sharing this target's texture.
+    pub fn sprite(&self) -> Sprite {
+        Sprite {
+            dimensions: self.dimensions,
+            tex_coords: self.tex_coords,
+            texture: self.texture.clone(),
+        }
     }
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct TextureVertex {
-    position: [f32; 3],
-    tex_coords: [f32; 2],
+    position: [f32; 2],
 }
 
 impl TextureVertex {
@@ -262,16 +446,126 @@ impl TextureVertex {
         wgpu::VertexBufferLayout {
             array_stride: mem::size_of::<TextureVertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x2,
+            }],
+        }
+    }
+}
+
+// The unit quad every sprite is expanded from. Corners are at 0/1 with matching
+// 0/1 UVs so the shader can `mix` them against the per-instance rects.
+const QUAD_VERTICES: &[TextureVertex] = &[
+    TextureVertex {
+        position: [0.0, 0.0],
+    },
+    TextureVertex {
+        position: [0.0, 1.0],
+    },
+    TextureVertex {
+        position: [1.0, 1.0],
+    },
+    TextureVertex {
+        position: [1.0, 0.0],
+    },
+];
+
+const QUAD_INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+/// A 2D affine transform applied to a sprite before the camera projection.
+///
+/// It is built from a `position`, a `rotation` in radians, a (possibly
+/// non-uniform) `scale` and a `pivot` point in world space that the rotation
+/// and scale are applied around. The [`Default`] value is the identity, so an
+/// axis-aligned draw keeps behaving exactly as before.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    pub position: (f32, f32),
+    pub rotation: f32,
+    pub scale: (f32, f32),
+    pub pivot: (f32, f32),
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            position: (0.0, 0.0),
+            rotation: 0.0,
+            scale: (1.0, 1.0),
+            pivot: (0.0, 0.0),
+        }
+    }
+}
+
+impl Transform {
+    /// Collapses the transform into the two linear columns and the translation
+    /// column the shader multiplies each quad corner by.
+    fn to_matrix(self) -> [[f32; 2]; 3] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sx, sy) = self.scale;
+        let (px, py) = self.pivot;
+        let (tx, ty) = self.position;
+
+        let a = cos * sx;
+        let b = -sin * sy;
+        let d = sin * sx;
+        let e = cos * sy;
+
+        [
+            [a, d],
+            [b, e],
+            [px - a * px - b * py + tx, py - d * px - e * py + ty],
+        ]
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct TextureInstance {
+    destination: [f32; 4],
+    tex_coords: [f32; 4],
+    transform: [[f32; 2]; 3],
+    depth: f32,
+}
+
+impl TextureInstance {
+    pub(crate) fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<TextureInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
                 wgpu::VertexAttribute {
                     offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2, // NEW!
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 14]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
                 },
             ],
         }
@@ -279,11 +573,50 @@ impl TextureVertex {
 }
 
 pub(crate) struct TextureRenderer {
-    shader: ShaderModule,
-    sampler: Sampler,
     texture_bind_group_layout: BindGroupLayout,
-    render_pipeline_layout: PipelineLayout,
-    vertex_buffer: Vec<(Buffer, Rc<Texture>, Vec<u16>, Buffer)>,
+    render_pipeline: RenderPipeline,
+    blit_pipeline: RenderPipeline,
+    blit_sampler: Sampler,
+    quad_vertex_buffer: Buffer,
+    quad_index_buffer: Buffer,
+    instance_buffers: Vec<InstanceBuffer>,
+    draws: Vec<(usize, Rc<Texture>, u32)>,
+}
+
+/// A persistent per-group instance buffer that grows on demand and is reused
+/// across frames, so heavy sprite counts don't allocate a fresh buffer every
+/// render.
+struct InstanceBuffer {
+    buffer: Buffer,
+    capacity: u64,
+}
+
+impl InstanceBuffer {
+    fn create_buffer(context: &WgpuContext, capacity: u64) -> Buffer {
+        context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Buffer"),
+            size: capacity,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn with_capacity(context: &WgpuContext, size: u64) -> Self {
+        let capacity = size.max(1);
+        InstanceBuffer {
+            buffer: Self::create_buffer(context, capacity),
+            capacity,
+        }
+    }
+
+    /// Reallocates with a doubling strategy when `size` no longer fits.
+    fn ensure_capacity(&mut self, context: &WgpuContext, size: u64) {
+        if self.capacity < size {
+            let capacity = size.max(self.capacity * 2);
+            self.buffer = Self::create_buffer(context, capacity);
+            self.capacity = capacity;
+        }
+    }
 }
 
 impl TextureRenderer {
@@ -339,23 +672,216 @@ impl TextureRenderer {
                     push_constant_ranges: &[],
                 });
 
-        let sampler = context.device.create_sampler(&wgpu::SamplerDescriptor {
+        // Linear sampler used only to downsample each mip level from the
+        // previous one during mipmap generation.
+        let blit_sampler = context.device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
             ..Default::default()
         });
 
+        let render_pipeline =
+            context
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Texture Render Pipeline"),
+                    layout: Some(&render_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main", // 1.
+                        buffers: &[
+                            TextureVertex::description(),
+                            TextureInstance::description(),
+                        ], // 2.
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        // 3.
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[wgpu::ColorTargetState {
+                            // 4.
+                            format: context.config.format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList, // 1.
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw, // 2.
+                        // A sprite under an arbitrary affine transform (e.g. a
+                        // negative scale used to flip it) has no reliable
+                        // winding, so the pipeline has to be two-sided.
+                        cull_mode: None,
+                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        // Requires Features::DEPTH_CLAMPING
+                        clamp_depth: false,
+                        // Requires Features::CONSERVATIVE_RASTERIZATION
+                        conservative: false,
+                    },
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: DepthTexture::DEPTH_FORMAT,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::GreaterEqual, // 1.
+                        stencil: wgpu::StencilState::default(),             // 2.
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState {
+                        count: 1,                         // 2.
+                        mask: !0,                         // 3.
+                        alpha_to_coverage_enabled: false, // 4.
+                    },
+                });
+
+        let blit_shader = context
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Blit Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/blit.wgsl").into()),
+            });
+
+        let blit_pipeline_layout =
+            context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Blit Pipeline Layout"),
+                    bind_group_layouts: &[&texture_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let blit_pipeline =
+            context
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Mipmap Blit Pipeline"),
+                    layout: Some(&blit_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &blit_shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &blit_shader,
+                        entry_point: "fs_main",
+                        targets: &[wgpu::ColorTargetState {
+                            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                            blend: Some(wgpu::BlendState::REPLACE),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        clamp_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                });
+
+        let quad_vertex_buffer =
+            context
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Quad Vertex Buffer"),
+                    contents: bytemuck::cast_slice(QUAD_VERTICES),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+        let quad_index_buffer =
+            context
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Quad Index Buffer"),
+                    contents: bytemuck::cast_slice(QUAD_INDICES),
+                    usage: wgpu::BufferUsages::INDEX,
+                });
+
         TextureRenderer {
-            shader,
-            sampler,
             texture_bind_group_layout,
-            render_pipeline_layout,
-            vertex_buffer: vec![],
+            render_pipeline,
+            blit_pipeline,
+            blit_sampler,
+            quad_vertex_buffer,
+            quad_index_buffer,
+            instance_buffers: vec![],
+            draws: vec![],
+        }
+    }
+
+    /// Fills every mip level below the base by repeatedly downsampling the
+    /// previous level with the linear blit pipeline.
+    fn generate_mipmaps(
+        &self,
+        context: &WgpuContext,
+        texture: &wgpu::Texture,
+        mip_level_count: u32,
+    ) {
+        let views: Vec<wgpu::TextureView> = (0..mip_level_count)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("mip"),
+                    base_mip_level: level,
+                    mip_level_count: std::num::NonZeroU32::new(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Mipmap Encoder"),
+            });
+
+        for level in 1..mip_level_count as usize {
+            let bind_group = context
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.texture_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&views[level - 1]),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&self.blit_sampler),
+                        },
+                    ],
+                    label: Some("mip_bind_group"),
+                });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &views[level],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.blit_pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
         }
+
+        context.queue.submit(std::iter::once(encoder.finish()));
     }
 
     pub(crate) fn render<'a>(
@@ -365,7 +891,7 @@ impl TextureRenderer {
         camera: &'a Camera,
         operations: &Vec<DrawTextureOperation>,
     ) {
-        self.vertex_buffer.clear();
+        self.draws.clear();
         let sorted_op = operations.iter().into_group_map_by(|op| op.index);
         for key in sorted_op.keys().into_iter().sorted() {
             if let Some(operations) = sorted_op.get(key) {
@@ -374,89 +900,54 @@ impl TextureRenderer {
                     None => continue,
                 };
 
-                let vertices: Vec<_> = operations
+                let instances: Vec<TextureInstance> = operations
                     .iter()
-                    .flat_map(|operation| {
-                        let depth = renderer::depth(operation.index);
-                        [
-                            TextureVertex {
-                                position: [
-                                    operation.destination.left,
-                                    operation.destination.top,
-                                    depth,
-                                ],
-                                tex_coords: [operation.tex_coords.left, operation.tex_coords.top],
-                            },
-                            TextureVertex {
-                                position: [
-                                    operation.destination.left,
-                                    operation.destination.bottom,
-                                    depth,
-                                ],
-                                tex_coords: [
-                                    operation.tex_coords.left,
-                                    operation.tex_coords.bottom,
-                                ],
-                            },
-                            TextureVertex {
-                                position: [
-                                    operation.destination.right,
-                                    operation.destination.bottom,
-                                    depth,
-                                ],
-                                tex_coords: [
-                                    operation.tex_coords.right,
-                                    operation.tex_coords.bottom,
-                                ],
-                            },
-                            TextureVertex {
-                                position: [
-                                    operation.destination.right,
-                                    operation.destination.top,
-                                    depth,
-                                ],
-                                tex_coords: [operation.tex_coords.right, operation.tex_coords.top],
-                            },
-                        ]
-                    })
-                    .collect();
-
-                let indices: Vec<u16> = (0..operations.len())
-                    .flat_map(|index| {
-                        let step: u16 = index as u16 * 4;
-                        [step + 0, step + 1, step + 2, step + 2, step + 3, step + 0]
+                    .map(|operation| TextureInstance {
+                        destination: [
+                            operation.destination.left,
+                            operation.destination.top,
+                            operation.destination.right,
+                            operation.destination.bottom,
+                        ],
+                        tex_coords: [
+                            operation.tex_coords.left,
+                            operation.tex_coords.top,
+                            operation.tex_coords.right,
+                            operation.tex_coords.bottom,
+                        ],
+                        transform: operation.transform.to_matrix(),
+                        depth: renderer::depth(operation.index),
                     })
                     .collect();
 
-                let vertex_buffer =
-                    context
-                        .device
-                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Vertex Buffer"),
-                            contents: bytemuck::cast_slice(&vertices[..]),
-                            usage: wgpu::BufferUsages::VERTEX,
-                        });
-                let index_buffer =
-                    context
-                        .device
-                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Index Buffer"),
-                            contents: bytemuck::cast_slice(&indices[..]),
-                            usage: wgpu::BufferUsages::INDEX,
-                        });
-                self.vertex_buffer
-                    .push((vertex_buffer, texture, indices, index_buffer));
+                let contents = bytemuck::cast_slice(&instances[..]);
+                let group_index = self.draws.len();
+                // Reuse this group's buffer across frames, only allocating when
+                // its current capacity can't hold the instance data.
+                if group_index == self.instance_buffers.len() {
+                    self.instance_buffers
+                        .push(InstanceBuffer::with_capacity(context, contents.len() as u64));
+                } else {
+                    self.instance_buffers[group_index]
+                        .ensure_capacity(context, contents.len() as u64);
+                }
+                context
+                    .queue
+                    .write_buffer(&self.instance_buffers[group_index].buffer, 0, contents);
+
+                self.draws
+                    .push((group_index, texture, instances.len() as u32));
             }
         }
 
-        for (vertex_buffer, texture, indices, index_buffer) in &self.vertex_buffer {
-            let indice_count = indices.len() as u32;
-            render_pass.set_pipeline(&texture.render_pipeline);
-            render_pass.set_bind_group(0, &camera.camera_bind_group, &[]);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &camera.camera_bind_group, &[]);
+        for (group_index, texture, instance_count) in &self.draws {
             render_pass.set_bind_group(1, &texture.texture_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..indice_count, 0, 0..1);
+            render_pass.set_vertex_buffer(0, self.quad_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.instance_buffers[*group_index].buffer.slice(..));
+            render_pass.set_index_buffer(self.quad_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..*instance_count);
         }
     }
 }
\ No newline at end of file